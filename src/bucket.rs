@@ -1,96 +1,237 @@
-use std::time::Instant;
-use std::convert::TryInto;
+use crate::clock::{Clock, SystemClock};
 use std::cmp::min;
+use std::time::{Duration, Instant};
 
-pub struct Bucket {
+/// Fixed-point scale applied to token counts so fractional refill progress
+/// (less than one whole token) isn't discarded between calls.
+const TOKEN_MULTIPLIER: i64 = 256;
+
+/// A partial reconfiguration for a [`Bucket`], applied via
+/// [`Bucket::update`]. Fields left as `None` keep their current value.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BucketUpdate {
+    pub max_amount: Option<i32>,
+    pub refill_time: Option<Duration>,
+    pub refill_amount: Option<i32>,
+    /// If `true`, available tokens are reset to the (possibly new) `max_amount`.
+    /// If `false` (the default), the current token count is preserved, clamped
+    /// down to the new `max_amount` if it shrank.
+    pub reset_tokens: bool,
+}
+
+#[derive(Debug)]
+pub struct Bucket<C: Clock = SystemClock> {
     max_amount: i32,
-    refill_time: i32,
+    refill_time: Duration,
     refill_amount: i32,
-    available_tokens: i32,
+    /// Available tokens, scaled by `TOKEN_MULTIPLIER`.
+    available_tokens: i64,
     last_updated: Instant,
+    clock: C,
 }
 
-impl Bucket {
-    /// Initialize new bucket.
-    pub fn new(max_amount: i32, refill_time: i32, refill_amount: i32) -> Bucket {
+impl Bucket<SystemClock> {
+    /// Initialize new bucket, backed by the real wall clock.
+    pub fn new(max_amount: i32, refill_time: Duration, refill_amount: i32) -> Bucket<SystemClock> {
+        Bucket::new_with_clock(max_amount, refill_time, refill_amount, SystemClock)
+    }
+}
+
+impl<C: Clock> Bucket<C> {
+    /// Initialize new bucket driven by the given `clock`, letting tests assert
+    /// refill behavior deterministically without real sleeps.
+    pub fn new_with_clock(
+        max_amount: i32,
+        refill_time: Duration,
+        refill_amount: i32,
+        clock: C,
+    ) -> Bucket<C> {
         Bucket {
             max_amount,
             refill_amount,
             refill_time,
-            available_tokens: max_amount,
-            last_updated: Instant::now(),
+            available_tokens: max_amount as i64 * TOKEN_MULTIPLIER,
+            last_updated: clock.now(),
+            clock,
         }
     }
 
     /// Reset bucket available tokens to `max_amount`
     pub fn reset(&mut self) {
-        self.available_tokens = self.max_amount;
-        self.last_updated = Instant::now();
+        self.available_tokens = self.max_amount as i64 * TOKEN_MULTIPLIER;
+        self.last_updated = self.clock.now();
+    }
+
+    /// Reconfigures the bucket's limits at runtime (e.g. a plan/tier change),
+    /// without losing accumulated tokens. Any pending refill is first settled
+    /// against the *old* rate, so in-flight credit isn't lost, before the new
+    /// parameters take effect. If `max_amount` shrinks and `reset_tokens` is
+    /// `false`, available tokens are clamped down to the new `max_amount`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use rate_limiter::bucket::{Bucket, BucketUpdate};
+    /// let mut bucket = Bucket::new(5, Duration::from_secs(1), 1);
+    /// bucket.reduce(5);
+    /// bucket.update(BucketUpdate {
+    ///     max_amount: Some(50),
+    ///     ..Default::default()
+    /// });
+    /// assert_eq!(bucket.get_available_tokens(), 0);
+    /// ```
+    pub fn update(&mut self, update: BucketUpdate) {
+        // settle any pending refill against the old rate before it changes
+        self.refill();
+
+        if let Some(max_amount) = update.max_amount {
+            self.max_amount = max_amount;
+        }
+        if let Some(refill_time) = update.refill_time {
+            self.refill_time = refill_time;
+        }
+        if let Some(refill_amount) = update.refill_amount {
+            self.refill_amount = refill_amount;
+        }
+
+        let max_scaled = self.max_amount as i64 * TOKEN_MULTIPLIER;
+        self.available_tokens = if update.reset_tokens {
+            max_scaled
+        } else {
+            min(self.available_tokens, max_scaled)
+        };
+        self.last_updated = self.clock.now();
     }
 
-    fn get_refill_tokens(&self) -> i32 {
-        let since_last: i32 = self.last_updated.elapsed().as_secs().try_into().unwrap();
-        since_last / self.refill_time * self.refill_amount
+    /// Fractional tokens (scaled by `TOKEN_MULTIPLIER`) accrued since `last_updated`.
+    fn get_refill_tokens(&self) -> i64 {
+        let elapsed_nanos = self
+            .clock
+            .now()
+            .saturating_duration_since(self.last_updated)
+            .as_nanos() as i128;
+        let refill_time_nanos = self.refill_time.as_nanos() as i128;
+        (elapsed_nanos * self.refill_amount as i128 * TOKEN_MULTIPLIER as i128 / refill_time_nanos)
+            as i64
+    }
+
+    /// Credits accrued refill tokens, clamped to `max_amount`, and advances
+    /// `last_updated` only by the slice of time those credited tokens account
+    /// for, so any leftover sub-token remainder is preserved for next time.
+    fn refill(&mut self) {
+        let refill_tokens = self.get_refill_tokens();
+        if refill_tokens <= 0 {
+            return;
+        }
+        let max_scaled = self.max_amount as i64 * TOKEN_MULTIPLIER;
+        let capacity = max_scaled - self.available_tokens;
+        if capacity <= 0 {
+            self.last_updated = self.clock.now();
+            return;
+        }
+        let applied = min(refill_tokens, capacity);
+        self.available_tokens += applied;
+        let refill_time_nanos = self.refill_time.as_nanos() as i128;
+        let consumed_nanos = (applied as i128 * refill_time_nanos
+            / (self.refill_amount as i128 * TOKEN_MULTIPLIER as i128)) as u64;
+        self.last_updated += Duration::from_nanos(consumed_nanos);
     }
 
     /// Get available tokens
-    /// 
+    ///
     /// # Examples
-    /// 
+    ///
     /// ```
+    /// use std::time::Duration;
     /// use rate_limiter::bucket;
-    /// let bucket = bucket::Bucket::new(5, 2, 1);
+    /// let bucket = bucket::Bucket::new(5, Duration::from_secs(2), 1);
     /// assert_eq!(bucket.get_available_tokens(), 5);
     /// ```
     pub fn get_available_tokens(&self) -> i32 {
-        min(
-            self.max_amount,
-            self.available_tokens + self.get_refill_tokens()
-        )
+        let max_scaled = self.max_amount as i64 * TOKEN_MULTIPLIER;
+        let available = min(max_scaled, self.available_tokens + self.get_refill_tokens());
+        (available / TOKEN_MULTIPLIER) as i32
+    }
+
+    /// The bucket's own capacity, i.e. the largest number of tokens it can ever
+    /// report as available. This reflects any change made via [`update`](Bucket::update),
+    /// so it may differ from the limiter's default `max_amount`.
+    pub fn max_amount(&self) -> i32 {
+        self.max_amount
+    }
+
+    /// Whether the bucket has fully refilled back to its own `max_amount`, i.e.
+    /// it's safe to evict and recreate with identical observable state.
+    pub fn is_full(&self) -> bool {
+        self.get_available_tokens() >= self.max_amount
     }
 
     /// Tries reducing tokens in bucket for particular key. Returns (success, available_tokens)
     /// tuple. Success is `false` if there is not enough tokens, otherwise `true`. If
     /// success was `false`, tokens weren't removed.
-    /// 
+    ///
     /// # Examples
-    /// 
+    ///
     /// ```
+    /// use std::time::Duration;
     /// use rate_limiter::bucket;
-    /// let mut bucket = bucket::Bucket::new(5, 1, 1);
+    /// let mut bucket = bucket::Bucket::new(5, Duration::from_secs(1), 1);
     /// // reducing more tokens than available returns false
     /// let (success, available_tokens) = bucket.reduce(6);
     /// assert!(!success);
     /// assert_eq!(available_tokens, 5);
-    /// 
+    ///
     /// // reducing fewer tokens than available, returns true
     /// let (success, available_tokens) = bucket.reduce(1);
     /// assert!(success);
     /// assert_eq!(available_tokens, 4);
     /// ```
     pub fn reduce(&mut self, tokens: i32) -> (bool, i32) {
-        let refill_tokens = self.get_refill_tokens();
-        self.available_tokens += refill_tokens;
-        if self.available_tokens > self.max_amount {
-            self.reset();
+        self.refill();
+        let requested = tokens as i64 * TOKEN_MULTIPLIER;
+        if requested > self.available_tokens {
+            return (false, (self.available_tokens / TOKEN_MULTIPLIER) as i32);
         }
-        if tokens > self.available_tokens {
-            return (false, self.available_tokens);
+        self.available_tokens -= requested;
+        (true, (self.available_tokens / TOKEN_MULTIPLIER) as i32)
+    }
+
+    /// Duration the caller must wait before `tokens` can be reduced, or
+    /// `None` if they're available right now.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use rate_limiter::bucket;
+    /// let bucket = bucket::Bucket::new(5, Duration::from_secs(1), 1);
+    /// assert_eq!(bucket.time_until_available(5), None);
+    /// ```
+    pub fn time_until_available(&self, tokens: i32) -> Option<Duration> {
+        let max_scaled = self.max_amount as i64 * TOKEN_MULTIPLIER;
+        let available = min(max_scaled, self.available_tokens + self.get_refill_tokens());
+        let requested = tokens as i64 * TOKEN_MULTIPLIER;
+        let deficit = requested - available;
+        if deficit <= 0 {
+            return None;
         }
-        self.available_tokens -= tokens;
-        self.last_updated = Instant::now();
-        (true, self.available_tokens)
+        let refill_time_nanos = self.refill_time.as_nanos() as i128;
+        let refill_per_tick = self.refill_amount as i128 * TOKEN_MULTIPLIER as i128;
+        let nanos = (deficit as i128 * refill_time_nanos + refill_per_tick - 1) / refill_per_tick;
+        Some(Duration::from_nanos(nanos as u64))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::clock::FakeClock;
     use std::{thread, time};
 
     #[test]
     fn test_reducing_tokens() {
-        let mut bucket = Bucket::new(5, 1, 1);
+        let mut bucket = Bucket::new(5, Duration::from_secs(1), 1);
         let (success, available_tokens) = bucket.reduce(6);
         // this should return false because we can't remove 6 tokens when only 5 is available
         assert!(!success);
@@ -107,7 +248,7 @@ mod tests {
     #[test]
     fn test_refilling_tokens_max() {
         let max_tokens = 5;
-        let mut bucket = Bucket::new(max_tokens, 1, 1);
+        let mut bucket = Bucket::new(max_tokens, Duration::from_secs(1), 1);
         // reduce 1 token
         bucket.reduce(1);
         // wait 2 seconds
@@ -118,7 +259,7 @@ mod tests {
 
     #[test]
     fn test_refill_time() {
-        let mut bucket = Bucket::new(5, 2, 1);
+        let mut bucket = Bucket::new(5, Duration::from_secs(2), 1);
         // reduce to 0
         bucket.reduce(5);
         // wait 2 seconds
@@ -129,7 +270,7 @@ mod tests {
 
     #[test]
     fn test_refill_amount() {
-        let mut bucket = Bucket::new(5, 1, 2);
+        let mut bucket = Bucket::new(5, Duration::from_secs(1), 2);
         // reduce to 0
         bucket.reduce(5);
         // wait 1 second
@@ -137,4 +278,110 @@ mod tests {
         // ensure we got 2 new tokens available
         assert_eq!(bucket.get_available_tokens(), 2)
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_time_until_available() {
+        let mut bucket = Bucket::new(5, Duration::from_secs(2), 1);
+        // tokens already available require no wait
+        assert_eq!(bucket.time_until_available(5), None);
+
+        // drain the bucket, then ask for one token back
+        bucket.reduce(5);
+        assert_eq!(
+            bucket.time_until_available(1),
+            Some(time::Duration::from_secs(2))
+        );
+    }
+
+    #[test]
+    fn test_sub_second_refill() {
+        let mut bucket = Bucket::new(5, Duration::from_millis(100), 1);
+        // reduce to 0
+        bucket.reduce(5);
+        // wait less than a full refill period
+        thread::sleep(time::Duration::from_millis(50));
+        // no whole token should have accrued yet
+        assert_eq!(bucket.get_available_tokens(), 0);
+        // wait past the refill period
+        thread::sleep(time::Duration::from_millis(60));
+        assert_eq!(bucket.get_available_tokens(), 1);
+    }
+
+    #[test]
+    fn test_fake_clock_refill_without_sleeping() {
+        let clock = FakeClock::new();
+        let mut bucket = Bucket::new_with_clock(5, Duration::from_secs(2), 1, &clock);
+        bucket.reduce(5);
+
+        // clock hasn't moved, so no tokens have refilled
+        assert_eq!(bucket.get_available_tokens(), 0);
+
+        clock.advance(Duration::from_secs(2));
+        assert_eq!(bucket.get_available_tokens(), 1);
+    }
+
+    #[test]
+    fn test_fake_clock_sub_second_precision() {
+        let clock = FakeClock::new();
+        let mut bucket = Bucket::new_with_clock(5, Duration::from_millis(100), 1, &clock);
+        bucket.reduce(5);
+
+        clock.advance(Duration::from_millis(50));
+        assert_eq!(bucket.get_available_tokens(), 0);
+
+        clock.advance(Duration::from_millis(60));
+        assert_eq!(bucket.get_available_tokens(), 1);
+    }
+
+    #[test]
+    fn test_update_preserves_accumulated_tokens() {
+        let mut bucket = Bucket::new(5, Duration::from_secs(1), 1);
+        bucket.reduce(2);
+        // promote to a higher tier without losing the 3 tokens already available
+        bucket.update(BucketUpdate {
+            max_amount: Some(50),
+            refill_amount: Some(10),
+            ..Default::default()
+        });
+        assert_eq!(bucket.get_available_tokens(), 3);
+    }
+
+    #[test]
+    fn test_update_clamps_tokens_when_max_amount_shrinks() {
+        let mut bucket = Bucket::new(50, Duration::from_secs(1), 1);
+        assert_eq!(bucket.get_available_tokens(), 50);
+        bucket.update(BucketUpdate {
+            max_amount: Some(5),
+            ..Default::default()
+        });
+        assert_eq!(bucket.get_available_tokens(), 5);
+    }
+
+    #[test]
+    fn test_is_full_reflects_bucket_own_max_amount_after_update() {
+        let mut bucket = Bucket::new(5, Duration::from_secs(1), 1);
+        assert!(bucket.is_full());
+
+        // promote to a higher tier and drain most of it; the bucket is far from
+        // full even though 10 tokens would have been "full" at the old tier
+        bucket.update(BucketUpdate {
+            max_amount: Some(100),
+            reset_tokens: true,
+            ..Default::default()
+        });
+        bucket.reduce(90);
+        assert_eq!(bucket.max_amount(), 100);
+        assert!(!bucket.is_full());
+    }
+
+    #[test]
+    fn test_update_can_reset_tokens() {
+        let mut bucket = Bucket::new(5, Duration::from_secs(1), 1);
+        bucket.reduce(5);
+        bucket.update(BucketUpdate {
+            reset_tokens: true,
+            ..Default::default()
+        });
+        assert_eq!(bucket.get_available_tokens(), 5);
+    }
+}
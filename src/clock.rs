@@ -0,0 +1,77 @@
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
+/// Source of the current time for a [`Bucket`](crate::bucket::Bucket). Lets refill
+/// math be driven by something other than the real wall clock, so tests can assert
+/// deterministic behavior without real sleeps.
+pub trait Clock {
+    fn now(&self) -> Instant;
+}
+
+/// The real wall clock, used by default.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock that only moves forward when [`advance`](FakeClock::advance) is called,
+/// for deterministic tests of refill behavior at any granularity.
+#[derive(Debug)]
+pub struct FakeClock {
+    now: Cell<Instant>,
+}
+
+impl FakeClock {
+    pub fn new() -> FakeClock {
+        FakeClock {
+            now: Cell::new(Instant::now()),
+        }
+    }
+
+    /// Moves the clock forward by `duration`.
+    pub fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+}
+
+impl Default for FakeClock {
+    fn default() -> Self {
+        FakeClock::new()
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> Instant {
+        self.now.get()
+    }
+}
+
+impl<C: Clock> Clock for &C {
+    fn now(&self) -> Instant {
+        (*self).now()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fake_clock_does_not_advance_on_its_own() {
+        let clock = FakeClock::new();
+        let initial = clock.now();
+        assert_eq!(clock.now(), initial);
+    }
+
+    #[test]
+    fn test_fake_clock_advances_by_requested_duration() {
+        let clock = FakeClock::new();
+        let initial = clock.now();
+        clock.advance(Duration::from_millis(500));
+        assert_eq!(clock.now(), initial + Duration::from_millis(500));
+    }
+}
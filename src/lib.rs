@@ -1,5 +1,9 @@
 pub mod bucket;
+pub mod clock;
 pub mod rate_limiter;
 
 pub use crate::rate_limiter::AtomicRateLimiter;
+#[cfg(feature = "async")]
+pub use crate::rate_limiter::AsyncAtomicRateLimiter;
 pub use crate::rate_limiter::RateLimiter;
+pub use crate::rate_limiter::TokenType;
@@ -2,14 +2,27 @@ use crate::bucket;
 use std::{
     collections::HashMap,
     sync::{Mutex, RwLock},
+    thread,
+    time::Duration,
 };
 
+/// Which dimension a bucket in a two-dimensional (`reduce_io`) throttle is tracking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenType {
+    /// An operation/request-count bucket.
+    Ops,
+    /// A bandwidth (byte throughput) bucket.
+    Bytes,
+}
+
 #[derive(Debug)]
 pub struct RateLimiter {
     default_max_amount: i32,
     default_refill_time: i32,
     default_refill_amount: i32,
+    default_bandwidth: Option<(i32, i32, i32)>,
     buckets: HashMap<String, bucket::Bucket>,
+    bandwidth_buckets: HashMap<String, bucket::Bucket>,
 }
 
 impl RateLimiter {
@@ -24,10 +37,19 @@ impl RateLimiter {
             default_max_amount,
             default_refill_time,
             default_refill_amount,
+            default_bandwidth: None,
             buckets: HashMap::new(),
+            bandwidth_buckets: HashMap::new(),
         }
     }
 
+    /// Configure a bandwidth (bytes-per-`refill_time`) bucket alongside the existing
+    /// operations bucket, so [`reduce_io`](Self::reduce_io) can throttle on both at once.
+    pub fn with_bandwidth(mut self, max_amount: i32, refill_time: i32, refill_amount: i32) -> Self {
+        self.default_bandwidth = Some((max_amount, refill_time, refill_amount));
+        self
+    }
+
     /// Returns `available_tokens` in bucket for given key. If bucket is not found, it returns
     /// `default_max_amount`.
     ///
@@ -66,13 +88,156 @@ impl RateLimiter {
         }
         let mut bucket = bucket::Bucket::new(
             self.default_max_amount,
-            self.default_refill_time,
+            Duration::from_secs(self.default_refill_time as u64),
             self.default_refill_amount,
         );
         let result = bucket.reduce(reduce_tokens);
         self.buckets.insert(key, bucket);
         result
     }
+
+    /// Duration the caller must wait before `tokens` can be reduced for `key`, or
+    /// `None` if they're available right now.
+    ///
+    /// # Examples
+    /// ```
+    /// use rate_limiter;
+    /// let mut rate_limiter = rate_limiter::RateLimiter::new(5, 2, 1);
+    /// assert_eq!(rate_limiter.time_until_available(String::from("some key"), 5), None);
+    /// ```
+    pub fn time_until_available(&self, key: String, tokens: i32) -> Option<Duration> {
+        match self.buckets.get(&key) {
+            Some(bucket) => bucket.time_until_available(tokens),
+            None => None,
+        }
+    }
+
+    /// Blocks the current thread, sleeping as needed, until `tokens` can be reduced
+    /// for `key`, then reduces them.
+    ///
+    /// # Panics
+    /// Panics if `tokens` exceeds the bucket's `max_amount`, since the request
+    /// could never be satisfied and would otherwise block forever.
+    ///
+    /// # Examples
+    /// ```
+    /// use rate_limiter;
+    /// let mut rate_limiter = rate_limiter::RateLimiter::new(5, 1, 1);
+    /// rate_limiter.take(String::from("some key"), 5);
+    /// assert_eq!(rate_limiter.get_available_tokens(String::from("some key")), 0);
+    /// ```
+    pub fn take(&mut self, key: String, tokens: i32) {
+        self.ensure_ops_bucket(&key);
+        let max_amount = self
+            .buckets
+            .get(&key)
+            .expect("bucket was just ensured")
+            .max_amount();
+        assert!(
+            tokens <= max_amount,
+            "cannot take {tokens} tokens: bucket max_amount is {max_amount}, so this request could never succeed"
+        );
+        loop {
+            let (success, _) = self.reduce(key.clone(), tokens);
+            if success {
+                return;
+            }
+            if let Some(wait) = self.time_until_available(key.clone(), tokens) {
+                thread::sleep(wait);
+            }
+        }
+    }
+
+    /// Returns available tokens of the given `token_type` for `key`. Querying
+    /// `TokenType::Bytes` requires [`with_bandwidth`](Self::with_bandwidth) to have
+    /// been configured.
+    pub fn get_available_tokens_of(&self, key: String, token_type: TokenType) -> i32 {
+        match token_type {
+            TokenType::Ops => self.get_available_tokens(key),
+            TokenType::Bytes => match self.bandwidth_buckets.get(&key) {
+                Some(bucket) => bucket.get_available_tokens(),
+                None => self
+                    .default_bandwidth
+                    .expect("with_bandwidth() must be called before using Bytes tokens")
+                    .0,
+            },
+        }
+    }
+
+    fn ensure_ops_bucket(&mut self, key: &str) {
+        if !self.buckets.contains_key(key) {
+            self.buckets.insert(
+                key.to_string(),
+                bucket::Bucket::new(
+                    self.default_max_amount,
+                    Duration::from_secs(self.default_refill_time as u64),
+                    self.default_refill_amount,
+                ),
+            );
+        }
+    }
+
+    fn ensure_bandwidth_bucket(&mut self, key: &str) {
+        let (max_amount, refill_time, refill_amount) = self
+            .default_bandwidth
+            .expect("with_bandwidth() must be called before using reduce_io");
+        if !self.bandwidth_buckets.contains_key(key) {
+            self.bandwidth_buckets.insert(
+                key.to_string(),
+                bucket::Bucket::new(max_amount, Duration::from_secs(refill_time as u64), refill_amount),
+            );
+        }
+    }
+
+    /// Tries reducing `ops` from the per-key operations bucket and `bytes` from the
+    /// per-key bandwidth bucket in a single all-or-nothing step, for workloads (e.g.
+    /// block-device-style I/O) that must be budgeted on both a request-count limit
+    /// and a throughput limit at once. Returns `(success, available_ops, available_bytes)`;
+    /// if either bucket lacks capacity, neither is reduced. Requires
+    /// [`with_bandwidth`](Self::with_bandwidth) to have been called.
+    ///
+    /// # Examples
+    /// ```
+    /// use rate_limiter;
+    /// let mut rate_limiter = rate_limiter::RateLimiter::new(5, 1, 1).with_bandwidth(1000, 1, 1000);
+    /// assert!(rate_limiter.reduce_io(String::from("some key"), 1, 500).0);
+    /// assert!(!rate_limiter.reduce_io(String::from("some key"), 10, 1).0);
+    /// ```
+    pub fn reduce_io(&mut self, key: String, ops: i32, bytes: i32) -> (bool, i32, i32) {
+        self.ensure_ops_bucket(&key);
+        self.ensure_bandwidth_bucket(&key);
+
+        let ops_available = self.buckets.get(&key).unwrap().get_available_tokens();
+        let bytes_available = self.bandwidth_buckets.get(&key).unwrap().get_available_tokens();
+        if ops > ops_available || bytes > bytes_available {
+            return (false, ops_available, bytes_available);
+        }
+
+        let (_, ops_available) = self.buckets.get_mut(&key).unwrap().reduce(ops);
+        let (_, bytes_available) = self.bandwidth_buckets.get_mut(&key).unwrap().reduce(bytes);
+        (true, ops_available, bytes_available)
+    }
+
+    /// Reconfigures the operations bucket's limits for `key` at runtime (e.g. a
+    /// plan/tier change), without losing accumulated tokens. If `key` has no
+    /// bucket yet, one is created with default parameters first.
+    ///
+    /// # Examples
+    /// ```
+    /// use rate_limiter;
+    /// use rate_limiter::bucket::BucketUpdate;
+    /// let mut rate_limiter = rate_limiter::RateLimiter::new(5, 1, 1);
+    /// rate_limiter.update(String::from("some key"), BucketUpdate {
+    ///     max_amount: Some(50),
+    ///     reset_tokens: true,
+    ///     ..Default::default()
+    /// });
+    /// assert_eq!(rate_limiter.get_available_tokens(String::from("some key")), 50);
+    /// ```
+    pub fn update(&mut self, key: String, update: bucket::BucketUpdate) {
+        self.ensure_ops_bucket(&key);
+        self.buckets.get_mut(&key).unwrap().update(update);
+    }
 }
 
 #[derive(Debug)]
@@ -80,7 +245,9 @@ pub struct AtomicRateLimiter {
     default_max_amount: i32,
     default_refill_time: i32,
     default_refill_amount: i32,
+    default_bandwidth: Option<(i32, i32, i32)>,
     buckets: RwLock<HashMap<String, Mutex<bucket::Bucket>>>,
+    bandwidth_buckets: RwLock<HashMap<String, Mutex<bucket::Bucket>>>,
 }
 
 impl AtomicRateLimiter {
@@ -95,10 +262,19 @@ impl AtomicRateLimiter {
             default_max_amount,
             default_refill_time,
             default_refill_amount,
+            default_bandwidth: None,
             buckets: RwLock::new(HashMap::new()),
+            bandwidth_buckets: RwLock::new(HashMap::new()),
         }
     }
 
+    /// Configure a bandwidth (bytes-per-`refill_time`) bucket alongside the existing
+    /// operations bucket, so [`reduce_io`](Self::reduce_io) can throttle on both at once.
+    pub fn with_bandwidth(mut self, max_amount: i32, refill_time: i32, refill_amount: i32) -> Self {
+        self.default_bandwidth = Some((max_amount, refill_time, refill_amount));
+        self
+    }
+
     /// Returns `available_tokens` in bucket for given key. If bucket is not found, it returns
     /// `default_max_amount`.
     ///
@@ -153,13 +329,232 @@ impl AtomicRateLimiter {
         // if still no key, insert one
         let mut bucket = bucket::Bucket::new(
             self.default_max_amount,
-            self.default_refill_time,
+            Duration::from_secs(self.default_refill_time as u64),
             self.default_refill_amount,
         );
         let result = bucket.reduce(reduce_tokens);
         buckets.insert(key, Mutex::new(bucket));
         result
     }
+
+    /// Duration the caller must wait before `tokens` can be reduced for `key`, or
+    /// `None` if they're available right now.
+    ///
+    /// # Examples
+    /// ```
+    /// use rate_limiter;
+    /// let rate_limiter = rate_limiter::AtomicRateLimiter::new(5, 2, 1);
+    /// assert_eq!(rate_limiter.time_until_available(String::from("some key"), 5), None);
+    /// ```
+    pub fn time_until_available(&self, key: String, tokens: i32) -> Option<Duration> {
+        let buckets = self.buckets.read().expect("RWLock poisoned.");
+        match buckets.get(&key) {
+            Some(bucket) => bucket
+                .lock()
+                .expect("Mutex poisoned")
+                .time_until_available(tokens),
+            None => None,
+        }
+    }
+
+    /// Blocks the current thread, sleeping as needed, until `tokens` can be reduced
+    /// for `key`, then reduces them.
+    ///
+    /// # Panics
+    /// Panics if `tokens` exceeds the bucket's `max_amount`, since the request
+    /// could never be satisfied and would otherwise block forever.
+    ///
+    /// # Examples
+    /// ```
+    /// use rate_limiter;
+    /// let rate_limiter = rate_limiter::AtomicRateLimiter::new(5, 1, 1);
+    /// rate_limiter.take(String::from("some key"), 5);
+    /// assert_eq!(rate_limiter.get_available_tokens(String::from("some key")), 0);
+    /// ```
+    pub fn take(&self, key: String, tokens: i32) {
+        self.ensure_ops_bucket(&key);
+        let buckets = self.buckets.read().expect("RWLock poisoned.");
+        let max_amount = buckets
+            .get(&key)
+            .expect("bucket was just ensured")
+            .lock()
+            .expect("Mutex poisoned")
+            .max_amount();
+        drop(buckets);
+        assert!(
+            tokens <= max_amount,
+            "cannot take {tokens} tokens: bucket max_amount is {max_amount}, so this request could never succeed"
+        );
+        loop {
+            let (success, _) = self.reduce(key.clone(), tokens);
+            if success {
+                return;
+            }
+            if let Some(wait) = self.time_until_available(key.clone(), tokens) {
+                thread::sleep(wait);
+            }
+        }
+    }
+
+    /// Returns available tokens of the given `token_type` for `key`. Querying
+    /// `TokenType::Bytes` requires [`with_bandwidth`](Self::with_bandwidth) to have
+    /// been configured.
+    pub fn get_available_tokens_of(&self, key: String, token_type: TokenType) -> i32 {
+        match token_type {
+            TokenType::Ops => self.get_available_tokens(key),
+            TokenType::Bytes => {
+                let buckets = self.bandwidth_buckets.read().expect("RWLock poisoned.");
+                match buckets.get(&key) {
+                    Some(bucket) => bucket
+                        .lock()
+                        .expect("Mutex poisoned")
+                        .get_available_tokens(),
+                    None => self
+                        .default_bandwidth
+                        .expect("with_bandwidth() must be called before using Bytes tokens")
+                        .0,
+                }
+            }
+        }
+    }
+
+    fn ensure_ops_bucket(&self, key: &str) {
+        let buckets = self.buckets.read().expect("RWLock poisoned.");
+        if buckets.contains_key(key) {
+            return;
+        }
+        drop(buckets);
+        let mut buckets = self.buckets.write().expect("RWLock poisoned.");
+        if buckets.contains_key(key) {
+            return;
+        }
+        buckets.insert(
+            key.to_string(),
+            Mutex::new(bucket::Bucket::new(
+                self.default_max_amount,
+                Duration::from_secs(self.default_refill_time as u64),
+                self.default_refill_amount,
+            )),
+        );
+    }
+
+    fn ensure_bandwidth_bucket(&self, key: &str) {
+        let buckets = self.bandwidth_buckets.read().expect("RWLock poisoned.");
+        if buckets.contains_key(key) {
+            return;
+        }
+        drop(buckets);
+        let mut buckets = self.bandwidth_buckets.write().expect("RWLock poisoned.");
+        if buckets.contains_key(key) {
+            return;
+        }
+        let (max_amount, refill_time, refill_amount) = self
+            .default_bandwidth
+            .expect("with_bandwidth() must be called before using reduce_io");
+        buckets.insert(
+            key.to_string(),
+            Mutex::new(bucket::Bucket::new(
+                max_amount,
+                Duration::from_secs(refill_time as u64),
+                refill_amount,
+            )),
+        );
+    }
+
+    /// Tries reducing `ops` from the per-key operations bucket and `bytes` from the
+    /// per-key bandwidth bucket in a single all-or-nothing step, for workloads (e.g.
+    /// block-device-style I/O) that must be budgeted on both a request-count limit
+    /// and a throughput limit at once. Returns `(success, available_ops, available_bytes)`;
+    /// if either bucket lacks capacity, neither is reduced. Requires
+    /// [`with_bandwidth`](Self::with_bandwidth) to have been called.
+    ///
+    /// # Examples
+    /// ```
+    /// use rate_limiter;
+    /// let rate_limiter = rate_limiter::AtomicRateLimiter::new(5, 1, 1).with_bandwidth(1000, 1, 1000);
+    /// assert!(rate_limiter.reduce_io(String::from("some key"), 1, 500).0);
+    /// assert!(!rate_limiter.reduce_io(String::from("some key"), 10, 1).0);
+    /// ```
+    pub fn reduce_io(&self, key: String, ops: i32, bytes: i32) -> (bool, i32, i32) {
+        self.ensure_ops_bucket(&key);
+        self.ensure_bandwidth_bucket(&key);
+
+        let ops_buckets = self.buckets.read().expect("RWLock poisoned.");
+        let bandwidth_buckets = self.bandwidth_buckets.read().expect("RWLock poisoned.");
+        let mut ops_bucket = ops_buckets
+            .get(&key)
+            .expect("bucket was just ensured")
+            .lock()
+            .expect("Mutex poisoned");
+        let mut bandwidth_bucket = bandwidth_buckets
+            .get(&key)
+            .expect("bucket was just ensured")
+            .lock()
+            .expect("Mutex poisoned");
+
+        let ops_available = ops_bucket.get_available_tokens();
+        let bytes_available = bandwidth_bucket.get_available_tokens();
+        if ops > ops_available || bytes > bytes_available {
+            return (false, ops_available, bytes_available);
+        }
+
+        let (_, ops_available) = ops_bucket.reduce(ops);
+        let (_, bytes_available) = bandwidth_bucket.reduce(bytes);
+        (true, ops_available, bytes_available)
+    }
+
+    /// Removes any bucket that has fully refilled back to its `max_amount`, bounding
+    /// memory use for high-cardinality keys (e.g. per-IP or per-user). Safe because a
+    /// fresh bucket created on the next `reduce`/`reduce_io` call behaves identically.
+    /// Takes the write lock once and drains in a single sweep.
+    ///
+    /// # Examples
+    /// ```
+    /// use rate_limiter;
+    /// let rate_limiter = rate_limiter::AtomicRateLimiter::new(5, 1, 1);
+    /// rate_limiter.reduce(String::from("some key"), 1);
+    /// rate_limiter.cleanup_full_buckets();
+    /// // bucket hadn't refilled yet, so it's still tracked
+    /// assert_eq!(rate_limiter.get_available_tokens(String::from("some key")), 4);
+    /// ```
+    pub fn cleanup_full_buckets(&self) {
+        let mut buckets = self.buckets.write().expect("RWLock poisoned.");
+        buckets.retain(|_, bucket| !bucket.get_mut().expect("Mutex poisoned").is_full());
+        drop(buckets);
+
+        if self.default_bandwidth.is_some() {
+            let mut bandwidth_buckets = self.bandwidth_buckets.write().expect("RWLock poisoned.");
+            bandwidth_buckets
+                .retain(|_, bucket| !bucket.get_mut().expect("Mutex poisoned").is_full());
+        }
+    }
+
+    /// Reconfigures the operations bucket's limits for `key` at runtime (e.g. a
+    /// plan/tier change), without losing accumulated tokens. If `key` has no
+    /// bucket yet, one is created with default parameters first.
+    ///
+    /// # Examples
+    /// ```
+    /// use rate_limiter;
+    /// use rate_limiter::bucket::BucketUpdate;
+    /// let rate_limiter = rate_limiter::AtomicRateLimiter::new(5, 1, 1);
+    /// rate_limiter.update(String::from("some key"), BucketUpdate {
+    ///     max_amount: Some(50),
+    ///     reset_tokens: true,
+    ///     ..Default::default()
+    /// });
+    /// assert_eq!(rate_limiter.get_available_tokens(String::from("some key")), 50);
+    /// ```
+    pub fn update(&self, key: String, update: bucket::BucketUpdate) {
+        self.ensure_ops_bucket(&key);
+        let buckets = self.buckets.read().expect("RWLock poisoned.");
+        buckets
+            .get(&key)
+            .expect("bucket was just ensured")
+            .lock()
+            .expect("Mutex poisoned")
+            .update(update);
+    }
 }
 
 #[cfg(feature = "async")]
@@ -168,9 +563,12 @@ pub struct AsyncAtomicRateLimiter {
     default_max_amount: i32,
     default_refill_time: i32,
     default_refill_amount: i32,
+    default_bandwidth: Option<(i32, i32, i32)>,
     buckets: tokio::sync::RwLock<HashMap<String, Mutex<bucket::Bucket>>>,
+    bandwidth_buckets: tokio::sync::RwLock<HashMap<String, Mutex<bucket::Bucket>>>,
 }
 
+#[cfg(feature = "async")]
 impl AsyncAtomicRateLimiter {
     /// Initialize AtomicRateLimiter with default parameters used when bucket for particular key is
     /// not present.
@@ -183,10 +581,19 @@ impl AsyncAtomicRateLimiter {
             default_max_amount,
             default_refill_time,
             default_refill_amount,
+            default_bandwidth: None,
             buckets: tokio::sync::RwLock::new(HashMap::new()),
+            bandwidth_buckets: tokio::sync::RwLock::new(HashMap::new()),
         }
     }
 
+    /// Configure a bandwidth (bytes-per-`refill_time`) bucket alongside the existing
+    /// operations bucket, so [`reduce_io`](Self::reduce_io) can throttle on both at once.
+    pub fn with_bandwidth(mut self, max_amount: i32, refill_time: i32, refill_amount: i32) -> Self {
+        self.default_bandwidth = Some((max_amount, refill_time, refill_amount));
+        self
+    }
+
     /// Returns `available_tokens` in bucket for given key. If bucket is not found, it returns
     /// `default_max_amount`.
     ///
@@ -247,13 +654,261 @@ impl AsyncAtomicRateLimiter {
         // if still no key, insert one
         let mut bucket = bucket::Bucket::new(
             self.default_max_amount,
-            self.default_refill_time,
+            Duration::from_secs(self.default_refill_time as u64),
             self.default_refill_amount,
         );
         let result = bucket.reduce(reduce_tokens);
         buckets.insert(key, Mutex::new(bucket));
         result
     }
+
+    /// Duration the caller must wait before `tokens` can be reduced for `key`, or
+    /// `None` if they're available right now.
+    ///
+    /// # Examples
+    /// ```
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     use rate_limiter;
+    ///     let rate_limiter = rate_limiter::AsyncAtomicRateLimiter::new(5, 2, 1);
+    ///     assert_eq!(rate_limiter.time_until_available(String::from("some key"), 5).await, None);
+    /// }
+    /// ```
+    pub async fn time_until_available(&self, key: String, tokens: i32) -> Option<Duration> {
+        let buckets = self.buckets.read().await;
+        match buckets.get(&key) {
+            Some(bucket) => bucket
+                .lock()
+                .expect("Mutex poisoned")
+                .time_until_available(tokens),
+            None => None,
+        }
+    }
+
+    /// Waits, asynchronously, until `tokens` can be reduced for `key`, then reduces them.
+    ///
+    /// # Panics
+    /// Panics if `tokens` exceeds the bucket's `max_amount`, since the request
+    /// could never be satisfied and would otherwise wait forever.
+    ///
+    /// # Examples
+    /// ```
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     use rate_limiter;
+    ///     let rate_limiter = rate_limiter::AsyncAtomicRateLimiter::new(5, 1, 1);
+    ///     rate_limiter.take(String::from("some key"), 5).await;
+    ///     assert_eq!(rate_limiter.get_available_tokens(String::from("some key")).await, 0);
+    /// }
+    /// ```
+    pub async fn take(&self, key: String, tokens: i32) {
+        self.ensure_ops_bucket(&key).await;
+        let buckets = self.buckets.read().await;
+        let max_amount = buckets
+            .get(&key)
+            .expect("bucket was just ensured")
+            .lock()
+            .expect("Mutex poisoned")
+            .max_amount();
+        drop(buckets);
+        assert!(
+            tokens <= max_amount,
+            "cannot take {tokens} tokens: bucket max_amount is {max_amount}, so this request could never succeed"
+        );
+        loop {
+            let (success, _) = self.reduce(key.clone(), tokens).await;
+            if success {
+                return;
+            }
+            if let Some(wait) = self.time_until_available(key.clone(), tokens).await {
+                tokio::time::sleep(wait).await;
+            }
+        }
+    }
+
+    /// Returns available tokens of the given `token_type` for `key`. Querying
+    /// `TokenType::Bytes` requires [`with_bandwidth`](Self::with_bandwidth) to have
+    /// been configured.
+    pub async fn get_available_tokens_of(&self, key: String, token_type: TokenType) -> i32 {
+        match token_type {
+            TokenType::Ops => self.get_available_tokens(key).await,
+            TokenType::Bytes => {
+                let buckets = self.bandwidth_buckets.read().await;
+                match buckets.get(&key) {
+                    Some(bucket) => bucket
+                        .lock()
+                        .expect("Mutex poisoned")
+                        .get_available_tokens(),
+                    None => self
+                        .default_bandwidth
+                        .expect("with_bandwidth() must be called before using Bytes tokens")
+                        .0,
+                }
+            }
+        }
+    }
+
+    async fn ensure_ops_bucket(&self, key: &str) {
+        let buckets = self.buckets.read().await;
+        if buckets.contains_key(key) {
+            return;
+        }
+        drop(buckets);
+        let mut buckets = self.buckets.write().await;
+        if buckets.contains_key(key) {
+            return;
+        }
+        buckets.insert(
+            key.to_string(),
+            Mutex::new(bucket::Bucket::new(
+                self.default_max_amount,
+                Duration::from_secs(self.default_refill_time as u64),
+                self.default_refill_amount,
+            )),
+        );
+    }
+
+    async fn ensure_bandwidth_bucket(&self, key: &str) {
+        let buckets = self.bandwidth_buckets.read().await;
+        if buckets.contains_key(key) {
+            return;
+        }
+        drop(buckets);
+        let mut buckets = self.bandwidth_buckets.write().await;
+        if buckets.contains_key(key) {
+            return;
+        }
+        let (max_amount, refill_time, refill_amount) = self
+            .default_bandwidth
+            .expect("with_bandwidth() must be called before using reduce_io");
+        buckets.insert(
+            key.to_string(),
+            Mutex::new(bucket::Bucket::new(
+                max_amount,
+                Duration::from_secs(refill_time as u64),
+                refill_amount,
+            )),
+        );
+    }
+
+    /// Tries reducing `ops` from the per-key operations bucket and `bytes` from the
+    /// per-key bandwidth bucket in a single all-or-nothing step, for workloads (e.g.
+    /// block-device-style I/O) that must be budgeted on both a request-count limit
+    /// and a throughput limit at once. Returns `(success, available_ops, available_bytes)`;
+    /// if either bucket lacks capacity, neither is reduced. Requires
+    /// [`with_bandwidth`](Self::with_bandwidth) to have been called.
+    ///
+    /// # Examples
+    /// ```
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     use rate_limiter;
+    ///     let rate_limiter = rate_limiter::AsyncAtomicRateLimiter::new(5, 1, 1).with_bandwidth(1000, 1, 1000);
+    ///     assert!(rate_limiter.reduce_io(String::from("some key"), 1, 500).await.0);
+    ///     assert!(!rate_limiter.reduce_io(String::from("some key"), 10, 1).await.0);
+    /// }
+    /// ```
+    pub async fn reduce_io(&self, key: String, ops: i32, bytes: i32) -> (bool, i32, i32) {
+        self.ensure_ops_bucket(&key).await;
+        self.ensure_bandwidth_bucket(&key).await;
+
+        let ops_buckets = self.buckets.read().await;
+        let bandwidth_buckets = self.bandwidth_buckets.read().await;
+        let mut ops_bucket = ops_buckets
+            .get(&key)
+            .expect("bucket was just ensured")
+            .lock()
+            .expect("Mutex poisoned");
+        let mut bandwidth_bucket = bandwidth_buckets
+            .get(&key)
+            .expect("bucket was just ensured")
+            .lock()
+            .expect("Mutex poisoned");
+
+        let ops_available = ops_bucket.get_available_tokens();
+        let bytes_available = bandwidth_bucket.get_available_tokens();
+        if ops > ops_available || bytes > bytes_available {
+            return (false, ops_available, bytes_available);
+        }
+
+        let (_, ops_available) = ops_bucket.reduce(ops);
+        let (_, bytes_available) = bandwidth_bucket.reduce(bytes);
+        (true, ops_available, bytes_available)
+    }
+
+    /// Removes any bucket that has fully refilled back to its `max_amount`, bounding
+    /// memory use for high-cardinality keys (e.g. per-IP or per-user). Safe because a
+    /// fresh bucket created on the next `reduce`/`reduce_io` call behaves identically.
+    /// Takes the write lock once and drains in a single sweep.
+    pub async fn cleanup_full_buckets(&self) {
+        let mut buckets = self.buckets.write().await;
+        buckets.retain(|_, bucket| !bucket.get_mut().expect("Mutex poisoned").is_full());
+        drop(buckets);
+
+        if self.default_bandwidth.is_some() {
+            let mut bandwidth_buckets = self.bandwidth_buckets.write().await;
+            bandwidth_buckets
+                .retain(|_, bucket| !bucket.get_mut().expect("Mutex poisoned").is_full());
+        }
+    }
+
+    /// Spawns a background task that calls [`cleanup_full_buckets`](Self::cleanup_full_buckets)
+    /// on a fixed `interval`. The task holds its own `Arc` to the limiter and runs until it is
+    /// aborted through the returned handle; dropping the handle does not stop it.
+    ///
+    /// # Examples
+    /// ```
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     use rate_limiter;
+    ///     use std::{sync::Arc, time::Duration};
+    ///     let rate_limiter = Arc::new(rate_limiter::AsyncAtomicRateLimiter::new(5, 1, 1));
+    ///     let cleanup = rate_limiter.clone().spawn_cleanup_task(Duration::from_secs(60));
+    ///     cleanup.abort();
+    /// }
+    /// ```
+    pub fn spawn_cleanup_task(
+        self: std::sync::Arc<Self>,
+        interval: Duration,
+    ) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                self.cleanup_full_buckets().await;
+            }
+        })
+    }
+
+    /// Reconfigures the operations bucket's limits for `key` at runtime (e.g. a
+    /// plan/tier change), without losing accumulated tokens. If `key` has no
+    /// bucket yet, one is created with default parameters first.
+    ///
+    /// # Examples
+    /// ```
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     use rate_limiter;
+    ///     use rate_limiter::bucket::BucketUpdate;
+    ///     let rate_limiter = rate_limiter::AsyncAtomicRateLimiter::new(5, 1, 1);
+    ///     rate_limiter.update(String::from("some key"), BucketUpdate {
+    ///         max_amount: Some(50),
+    ///         reset_tokens: true,
+    ///         ..Default::default()
+    ///     }).await;
+    ///     assert_eq!(rate_limiter.get_available_tokens(String::from("some key")).await, 50);
+    /// }
+    /// ```
+    pub async fn update(&self, key: String, update: bucket::BucketUpdate) {
+        self.ensure_ops_bucket(&key).await;
+        let buckets = self.buckets.read().await;
+        buckets
+            .get(&key)
+            .expect("bucket was just ensured")
+            .lock()
+            .expect("Mutex poisoned")
+            .update(update);
+    }
 }
 
 #[cfg(test)]
@@ -261,6 +916,95 @@ mod tests {
     use super::*;
     use std::{sync::Arc, thread};
 
+    #[test]
+    fn test_take_waits_for_tokens_to_refill() {
+        let mut rate_limiter = RateLimiter::new(1, 1, 1);
+        rate_limiter.reduce(String::from("some key"), 1);
+        // no tokens left; take() must actually wait out the refill, not just loop
+        rate_limiter.take(String::from("some key"), 1);
+        assert_eq!(
+            rate_limiter.get_available_tokens(String::from("some key")),
+            0
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "could never succeed")]
+    fn test_take_panics_when_tokens_exceed_max_amount() {
+        let mut rate_limiter = RateLimiter::new(2, 1, 1);
+        rate_limiter.take(String::from("some key"), 5);
+    }
+
+    #[test]
+    fn test_reduce_io_all_or_nothing() {
+        let mut rate_limiter = RateLimiter::new(5, 1, 1).with_bandwidth(1000, 1, 1000);
+
+        // plenty of ops and bytes available, should succeed
+        assert!(rate_limiter.reduce_io(String::from("some key"), 2, 500).0);
+
+        // enough ops but not enough bytes left, should fail and touch neither bucket
+        let (success, ops_available, bytes_available) =
+            rate_limiter.reduce_io(String::from("some key"), 1, 600);
+        assert!(!success);
+        assert_eq!(ops_available, 3);
+        assert_eq!(bytes_available, 500);
+
+        // both still have the untouched amounts from before the failed call
+        assert_eq!(
+            rate_limiter.get_available_tokens_of(String::from("some key"), TokenType::Ops),
+            3
+        );
+        assert_eq!(
+            rate_limiter.get_available_tokens_of(String::from("some key"), TokenType::Bytes),
+            500
+        );
+    }
+
+    #[test]
+    fn test_cleanup_full_buckets() {
+        let data = AtomicRateLimiter::new(5, 1, 1);
+
+        // untouched key is full, should be swept
+        data.reduce(String::from("full"), 0);
+        // drained key isn't full, should survive the sweep
+        data.reduce(String::from("drained"), 5);
+
+        assert_eq!(data.buckets.read().expect("RWLock poisoned.").len(), 2);
+
+        data.cleanup_full_buckets();
+
+        // the full bucket was actually dropped from the map, not just left alone
+        let buckets = data.buckets.read().expect("RWLock poisoned.");
+        assert_eq!(buckets.len(), 1);
+        assert!(buckets.contains_key("drained"));
+        drop(buckets);
+
+        // both keys still report their expected token counts, whether or not the
+        // underlying bucket was actually evicted (a fresh bucket is indistinguishable)
+        assert_eq!(data.get_available_tokens(String::from("full")), 5);
+        assert_eq!(data.get_available_tokens(String::from("drained")), 0);
+    }
+
+    #[test]
+    fn test_update_preserves_accumulated_tokens() {
+        let mut rate_limiter = RateLimiter::new(5, 1, 1);
+        rate_limiter.reduce(String::from("some key"), 2);
+
+        // promote to a higher tier; the 3 tokens already available aren't lost
+        rate_limiter.update(
+            String::from("some key"),
+            bucket::BucketUpdate {
+                max_amount: Some(50),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(
+            rate_limiter.get_available_tokens(String::from("some key")),
+            3
+        );
+    }
+
     #[test]
     fn test_reducing_tokens_atomic() {
         let data = Arc::new(AtomicRateLimiter::new(30, 1, 1));